@@ -1,10 +1,93 @@
-use crate::{ConversionResult, FloatLiteral};
-use core::ops;
+use core::fmt;
+
+use crate::{ConversionResult, FloatLiteral, FloatSuffix, RoundingMode};
+
+/// Whether the discarded bits of a conversion should round the kept
+/// mantissa up, for the given rounding mode.
+///
+/// `guard` is the highest discarded bit, `sticky` is the OR of every other
+/// discarded bit (including digits that never made it into the working
+/// register at all), and `mantissa_odd` is the lowest bit of the mantissa
+/// that's being kept.
+fn should_round_up(
+    rounding: RoundingMode,
+    is_positive: bool,
+    guard: bool,
+    sticky: bool,
+    mantissa_odd: bool,
+) -> bool {
+    match rounding {
+        RoundingMode::NearestTiesToEven => guard && (sticky || mantissa_odd),
+        RoundingMode::NearestTiesToAway => guard,
+        RoundingMode::TowardZero => false,
+        RoundingMode::TowardPositive => is_positive && (guard || sticky),
+        RoundingMode::TowardNegative => !is_positive && (guard || sticky),
+    }
+}
+
+/// Signed rounding error, in ULPs of the result, given the fraction of one
+/// (pre-rounding) ULP that was discarded (in `[0, 1)`) and whether the
+/// discarded bits caused a round-up.
+///
+/// `carried_exponent` must be set if rounding up carried out of the mantissa
+/// field and bumped the exponent, which doubles the size of an ULP; the
+/// error is halved to stay in units of the (now larger) result ULP rather
+/// than the pre-rounding one.
+///
+/// A positive result means the returned value is larger than the literal; a
+/// negative result means it's smaller.
+fn ulp_error(is_positive: bool, rounded_up: bool, carried_exponent: bool, fraction: f64) -> f64 {
+    let mut magnitude_error = if rounded_up {
+        1.0 - fraction
+    } else {
+        -fraction
+    };
+    if carried_exponent {
+        magnitude_error /= 2.0;
+    }
+    if is_positive {
+        magnitude_error
+    } else {
+        -magnitude_error
+    }
+}
+
+/// `ulp_error` for the overflow-to-infinity case, where the gap between the
+/// largest finite value and infinity isn't a finite number of ULPs.
+fn ulp_error_overflow(is_positive: bool) -> f64 {
+    if is_positive {
+        f64::INFINITY
+    } else {
+        f64::NEG_INFINITY
+    }
+}
+
+/// `ulp_error` for the case where the magnitude is below half the smallest
+/// subnormal, so guard/sticky bits are never computed (the shift would be
+/// too large to fit in the working register). The true error is under 0.5
+/// ULP of the smallest subnormal when the result is zero, and between 0.5
+/// and 1 ULP when it rounded up to the smallest subnormal; either way this
+/// reports 0.5 as a saturated approximation rather than an exact value.
+fn ulp_error_saturated_underflow(is_positive: bool, rounded_up: bool) -> f64 {
+    let magnitude_error = if rounded_up { 0.5 } else { -0.5 };
+    if is_positive {
+        magnitude_error
+    } else {
+        -magnitude_error
+    }
+}
 
 macro_rules! impl_fpformat {
-    ($fp_type:ty, $bits_type:ty, $exponent_bits: literal, $mantissa_bits: literal, $from_bits: expr, $infinity: expr, $max_exp: expr, $min_exp: expr) => {
+    ($fp_type:ty, $bits_type:ty, $exponent_bits: literal, $mantissa_bits: literal, $from_bits: expr, $to_bits: expr, $max_exp: expr, $min_exp: expr, $suffix: expr) => {
         impl FPFormat for $fp_type {
-            fn from_literal(literal: FloatLiteral) -> ConversionResult<$fp_type> {
+            fn suffix() -> Option<FloatSuffix> {
+                $suffix
+            }
+
+            fn from_literal_with_rounding(
+                literal: FloatLiteral,
+                rounding: RoundingMode,
+            ) -> ConversionResult<$fp_type> {
                 const EXPONENT_BITS: u32 = $exponent_bits;
                 const MANTISSA_BITS: u32 = $mantissa_bits;
 
@@ -13,21 +96,26 @@ macro_rules! impl_fpformat {
                 // The spec always gives an exponent bias that follows this formula.
                 const EXPONENT_BIAS: u32 = (1 << (EXPONENT_BITS - 1)) - 1;
 
+                let sign_result: $bits_type =
+                    (!literal.is_positive as $bits_type) << (MANTISSA_BITS + EXPONENT_BITS);
+
                 // 4 bits for each hexadecimal offset
                 let mut exponent_offset: i32 = literal.decimal_offset * 4;
 
                 // If there were all
                 if literal.digits.is_empty() {
-                    return ConversionResult::Precise(0.0);
+                    return ConversionResult::Precise($from_bits(sign_result));
                 }
 
                 // This code is a work of art.
                 let mut was_truncated = false;
                 let mut mantissa_result: $bits_type = 0;
                 for (index, digit) in literal.digits.iter().enumerate() {
-                    if index as u32 * 4 > MANTISSA_BITS {
-                        was_truncated = true;
-                        break;
+                    if index as u32 * 4 >= TOTAL_BITS {
+                        if *digit != 0 {
+                            was_truncated = true;
+                        }
+                        continue;
                     }
                     let mut digit_value = *digit as $bits_type;
                     digit_value <<= TOTAL_BITS - (index as u32 + 1) * 4;
@@ -36,39 +124,137 @@ macro_rules! impl_fpformat {
                 let leading_zeros = mantissa_result.leading_zeros();
                 exponent_offset -= leading_zeros as i32 + 1;
                 mantissa_result <<= leading_zeros + 1;
-                mantissa_result >>= TOTAL_BITS - MANTISSA_BITS;
 
-                let final_exponent = exponent_offset + literal.exponent;
+                // At this point `mantissa_result` holds the fraction bits that
+                // follow the implicit leading one, left-justified across the
+                // full `TOTAL_BITS` register. `DISCARD_BITS` (the width of the
+                // sign+exponent fields) is exactly how much of that has to be
+                // shifted away to land the fraction in the mantissa field.
+                const DISCARD_BITS: u32 = TOTAL_BITS - MANTISSA_BITS;
+
+                let mut final_exponent = exponent_offset + literal.exponent;
 
-                // Check for underflows
                 if final_exponent < $min_exp - 1 {
-                    // TODO: Implement subnormal numbers.
-                    if literal.is_positive {
-                        return ConversionResult::Imprecise(0.0);
+                    // Subnormal result: too small for an implicit leading one.
+                    // Shift the full significand (leading bit included) right
+                    // until it lines up with the smallest normal exponent,
+                    // keeping track of the bits shifted out for rounding.
+                    let shift = ($min_exp - 1) - final_exponent;
+                    if shift > (MANTISSA_BITS + 1) as i32 {
+                        // Every bit lies below the guard position, so only
+                        // the sticky bit is set; directed modes may still
+                        // round up to the smallest subnormal.
+                        let rounded_up =
+                            should_round_up(rounding, literal.is_positive, false, true, false);
+                        let magnitude: $bits_type = if rounded_up { 1 } else { 0 };
+                        return ConversionResult::Imprecise {
+                            value: $from_bits(sign_result | magnitude),
+                            ulp_error: ulp_error_saturated_underflow(
+                                literal.is_positive,
+                                rounded_up,
+                            ),
+                        };
+                    }
+                    let total_discard = DISCARD_BITS + shift as u32;
+
+                    let full_significand: u128 = (1u128 << TOTAL_BITS) | (mantissa_result as u128);
+                    let discarded = full_significand & ((1u128 << total_discard) - 1);
+                    let guard = discarded & (1u128 << (total_discard - 1)) != 0;
+                    let sticky =
+                        discarded & ((1u128 << (total_discard - 1)) - 1) != 0 || was_truncated;
+                    let mut mantissa_result = (full_significand >> total_discard) as $bits_type;
+
+                    // An exponent field of 0 means subnormal; it only becomes
+                    // nonzero here if rounding carries all the way up to the
+                    // smallest normal number, which is the correct result.
+                    let mut exponent_result: $bits_type = 0;
+                    let mantissa_odd = mantissa_result & 1 != 0;
+                    let rounded_up =
+                        should_round_up(rounding, literal.is_positive, guard, sticky, mantissa_odd);
+                    if rounded_up {
+                        mantissa_result += 1;
+                        if mantissa_result >> MANTISSA_BITS != 0 {
+                            mantissa_result = 0;
+                            exponent_result = 1 << MANTISSA_BITS;
+                        }
+                    }
+
+                    let float_value = $from_bits(sign_result | exponent_result | mantissa_result);
+
+                    return if guard || sticky {
+                        let fraction = discarded as f64 / (1u128 << total_discard) as f64;
+                        // Carrying from the largest subnormal into the
+                        // smallest normal doesn't change the ULP size, since
+                        // the two are defined to be contiguous.
+                        ConversionResult::Imprecise {
+                            value: float_value,
+                            ulp_error: ulp_error(literal.is_positive, rounded_up, false, fraction),
+                        }
                     } else {
-                        return ConversionResult::Imprecise(-0.0);
+                        ConversionResult::Precise(float_value)
                     };
                 }
 
-                // Check for overflows
+                // The final shift below discards the low `DISCARD_BITS` bits of
+                // `mantissa_result`. The highest of those is the "guard" bit; the
+                // rest are OR'd together (along with `was_truncated`, for digits
+                // that never made it into `mantissa_result` at all) to form the
+                // "sticky" bit. Together `should_round_up` uses these to apply
+                // whichever `RoundingMode` was requested.
+                let discarded = mantissa_result & (((1 as $bits_type) << DISCARD_BITS) - 1);
+                let guard = discarded & ((1 as $bits_type) << (DISCARD_BITS - 1)) != 0;
+                let sticky = discarded & (((1 as $bits_type) << (DISCARD_BITS - 1)) - 1) != 0
+                    || was_truncated;
+                mantissa_result >>= DISCARD_BITS;
+
+                let mantissa_odd = mantissa_result & 1 != 0;
+                let rounded_up =
+                    should_round_up(rounding, literal.is_positive, guard, sticky, mantissa_odd);
+                // Tracks whether rounding bumped the exponent, which doubles
+                // the size of an ULP; `ulp_error` needs to know so it can
+                // report the error in units of the result's (larger) ULP
+                // rather than the pre-rounding one.
+                let mut carried_exponent = false;
+                if rounded_up {
+                    mantissa_result += 1;
+                    if mantissa_result >> MANTISSA_BITS != 0 {
+                        // Rounding carried out of the mantissa field; bump the
+                        // exponent and reset the mantissa, same as a normal FP
+                        // carry.
+                        mantissa_result = 0;
+                        final_exponent += 1;
+                        carried_exponent = true;
+                    }
+                }
+
+                // Check for overflows. The infinity bit pattern (exponent all
+                // ones, mantissa zero) is assembled directly so that formats
+                // whose type doesn't expose an `INFINITY` constant work too.
                 if final_exponent > $max_exp - 1 {
-                    if literal.is_positive {
-                        return ConversionResult::Imprecise($infinity);
-                    } else {
-                        return ConversionResult::Imprecise(-$infinity);
+                    let infinity_bits: $bits_type =
+                        (((1 as $bits_type) << EXPONENT_BITS) - 1) << MANTISSA_BITS;
+                    return ConversionResult::Imprecise {
+                        value: $from_bits(sign_result | infinity_bits),
+                        ulp_error: ulp_error_overflow(literal.is_positive),
                     };
                 }
 
                 let exponent_result: $bits_type =
                     ((final_exponent + EXPONENT_BIAS as i32) as $bits_type) << MANTISSA_BITS;
 
-                let sign_result: $bits_type =
-                    (!literal.is_positive as $bits_type) << (MANTISSA_BITS + EXPONENT_BITS);
-
                 let float_value = $from_bits(sign_result | exponent_result | mantissa_result);
 
-                if was_truncated {
-                    ConversionResult::Imprecise(float_value)
+                if guard || sticky {
+                    let fraction = discarded as f64 / ((1 as $bits_type) << DISCARD_BITS) as f64;
+                    ConversionResult::Imprecise {
+                        value: float_value,
+                        ulp_error: ulp_error(
+                            literal.is_positive,
+                            rounded_up,
+                            carried_exponent,
+                            fraction,
+                        ),
+                    }
                 } else {
                     ConversionResult::Precise(float_value)
                 }
@@ -81,15 +267,113 @@ macro_rules! impl_fpformat {
                 // final_result |= mantissa_result;
                 // ConversionResult::Precise($from_bits(final_result))
             }
+
+            fn write_hex(self, f: &mut fmt::Formatter) -> fmt::Result {
+                const EXPONENT_BITS: u32 = $exponent_bits;
+                const MANTISSA_BITS: u32 = $mantissa_bits;
+                const EXPONENT_BIAS: i32 = (1i32 << (EXPONENT_BITS - 1)) - 1;
+                const EXPONENT_MASK: $bits_type = ((1 as $bits_type) << EXPONENT_BITS) - 1;
+                const MANTISSA_MASK: $bits_type = ((1 as $bits_type) << MANTISSA_BITS) - 1;
+                // Hex digits can only represent whole nibbles, so the
+                // mantissa is padded with trailing zero bits up to the next
+                // multiple of 4 before being split into digits.
+                const NIBBLE_PAD: u32 = (4 - MANTISSA_BITS % 4) % 4;
+                const NIBBLE_COUNT: usize = ((MANTISSA_BITS + NIBBLE_PAD) / 4) as usize;
+
+                let bits: $bits_type = $to_bits(self);
+                let is_negative = bits >> (EXPONENT_BITS + MANTISSA_BITS) != 0;
+                let exponent_field = (bits >> MANTISSA_BITS) & EXPONENT_MASK;
+                let mantissa_field = bits & MANTISSA_MASK;
+
+                if is_negative {
+                    write!(f, "-")?;
+                }
+
+                if exponent_field == EXPONENT_MASK {
+                    return if mantissa_field == 0 {
+                        write!(f, "inf")
+                    } else {
+                        write!(f, "nan")
+                    };
+                }
+
+                if exponent_field == 0 && mantissa_field == 0 {
+                    return write!(f, "0x0p+0");
+                }
+
+                // Subnormals have no implicit leading bit, and are printed
+                // using the same (fixed) exponent as the smallest normal.
+                let (leading_digit, unbiased_exponent) = if exponent_field == 0 {
+                    (0u8, 1 - EXPONENT_BIAS)
+                } else {
+                    (1u8, exponent_field as i32 - EXPONENT_BIAS)
+                };
+
+                let mut nibbles = [0u8; NIBBLE_COUNT];
+                let mut padded_mantissa = (mantissa_field as u128) << NIBBLE_PAD;
+                for nibble in nibbles.iter_mut().rev() {
+                    *nibble = (padded_mantissa & 0xf) as u8;
+                    padded_mantissa >>= 4;
+                }
+
+                // Trim the trailing zero nibbles so short mantissas (and
+                // exact powers of two) print as the shortest exact string.
+                let mut nibble_len = NIBBLE_COUNT;
+                while nibble_len > 0 && nibbles[nibble_len - 1] == 0 {
+                    nibble_len -= 1;
+                }
+
+                write!(f, "0x{:x}", leading_digit)?;
+                if nibble_len > 0 {
+                    write!(f, ".")?;
+                    for &nibble in &nibbles[..nibble_len] {
+                        write!(f, "{:x}", nibble)?;
+                    }
+                }
+                write!(f, "p{:+}", unbiased_exponent)
+            }
         }
     };
 }
 
 /// Trait to describe conversion to floating point formats.
-pub trait FPFormat: ops::Neg<Output = Self> + Sized + Copy {
-    /// Convert a literal to this format. This is a hack so that we can use
-    /// a macro to implement conversions.
-    fn from_literal(literal: FloatLiteral) -> ConversionResult<Self>;
+///
+/// Since conversion only ever needs to assemble a raw sign/exponent/mantissa
+/// bit pattern and hand it to a bits-to-value constructor, implementing this
+/// trait for another IEEE-754-shaped type inside this crate is just a matter
+/// of picking the right exponent/mantissa widths: see the `impl_fpformat!`
+/// invocations below. `impl_fpformat!` is not exported and the fields of
+/// [`FloatLiteral`] are private, so code outside this crate can't implement
+/// the conversion this way.
+pub trait FPFormat: Sized + Copy {
+    /// The C11/WGSL floating-suffix that selects this format (e.g. `f` for
+    /// `f32`), or `None` if this format has no corresponding suffix (e.g.
+    /// `half::bf16`). Used by [`FloatLiteral::convert_to_suffixed`].
+    fn suffix() -> Option<FloatSuffix>;
+
+    /// Convert a literal to this format using the given rounding mode. This
+    /// is a hack so that we can use a macro to implement conversions.
+    fn from_literal_with_rounding(
+        literal: FloatLiteral,
+        rounding: RoundingMode,
+    ) -> ConversionResult<Self>;
+
+    /// Convert a literal to this format, rounding to nearest with ties to
+    /// even.
+    fn from_literal(literal: FloatLiteral) -> ConversionResult<Self> {
+        Self::from_literal_with_rounding(literal, RoundingMode::NearestTiesToEven)
+    }
+
+    /// Write `self` as a canonical C99 `%a` hex-float string, e.g.
+    /// `0x1.5bf0a8p+6`. The output is always lowercase; there is no `%A`
+    /// form. Every IEEE-754 value is exactly representable in
+    /// hex, so unlike `Display` for a decimal float this never loses
+    /// precision, and the mantissa is printed with trailing zero hex digits
+    /// trimmed rather than padded to a fixed width.
+    ///
+    /// Use the [`HexFloat`](crate::HexFloat) wrapper to get a type that
+    /// implements `Display`/`LowerHex` via this method.
+    fn write_hex(self, f: &mut fmt::Formatter) -> fmt::Result;
 }
 
 impl_fpformat!(
@@ -98,9 +382,10 @@ impl_fpformat!(
     8,
     23,
     f32::from_bits,
-    core::f32::INFINITY,
-    core::f32::MAX_EXP,
-    core::f32::MIN_EXP
+    f32::to_bits,
+    f32::MAX_EXP,
+    f32::MIN_EXP,
+    Some(FloatSuffix::F32)
 );
 impl_fpformat!(
     f64,
@@ -108,7 +393,36 @@ impl_fpformat!(
     11,
     52,
     f64::from_bits,
-    core::f64::INFINITY,
-    core::f64::MAX_EXP,
-    core::f64::MIN_EXP
+    f64::to_bits,
+    f64::MAX_EXP,
+    f64::MIN_EXP,
+    Some(FloatSuffix::F64)
+);
+
+// `half`'s `f16`/`bf16` don't expose `MAX_EXP`/`MIN_EXP` constants like the
+// builtin float types do, so these are spelled out using the same formula
+// (`bias + 1` and `-bias + 2`, where `bias = 2^(exponent_bits - 1) - 1`).
+#[cfg(feature = "half")]
+impl_fpformat!(
+    half::f16,
+    u16,
+    5,
+    10,
+    half::f16::from_bits,
+    half::f16::to_bits,
+    16,
+    -13,
+    Some(FloatSuffix::F16)
+);
+#[cfg(feature = "half")]
+impl_fpformat!(
+    half::bf16,
+    u16,
+    8,
+    7,
+    half::bf16::from_bits,
+    half::bf16::to_bits,
+    128,
+    -125,
+    None
 );
@@ -18,14 +18,25 @@
 //!
 //! ## Features
 //! - No dependencies
-//! - Non-UTF-8 parser
-//! - Precision warnings
+//! - Non-UTF-8 parser, with a byte-slice fast path via [`FloatLiteral::from_bytes`]
+//!   that skips UTF-8 decoding entirely
+//! - Precision warnings, quantified in ULPs via [`ConversionResult::Imprecise`]
 //! - `no_std` support (MSRV 1.36.0)
+//! - Optional conversion to `f16`/`bf16` via the `half` crate
+//! - Selectable IEEE-754 rounding modes
+//! - Optional `_` digit separators via [`ParseOptions`]
+//! - Canonical `%a` hex-float formatting for native floats via [`HexFloat`],
+//!   and a normalized round-trip form for `FloatLiteral` (its first nonzero
+//!   hex digit goes before the point, so it isn't always `0x1.`)
+//! - Strict exact-or-error conversion via [`FloatLiteral::convert_exact`]
+//! - Optional C/WGSL floating-suffix parsing via [`ParseOptions::allow_suffix`]
+//! - Hex integer literals (`0xFFu32`), not just floats, via [`parse_number_literal`]
 //!
 //! ## Differences from the specification
 //! There are two places where hexponent differs from the C11 specificaiton.
 //! - An exponent is not required. (`0x1.2` is allowed)
-//! - `floating-suffix` is *not* parsed. (`0x1p4l` is not allowed)
+//! - `floating-suffix` is *not* parsed by default. (`0x1p4l` is not allowed
+//!   unless [`ParseOptions::allow_suffix`] is enabled)
 //!
 //! ## `no_std` support
 //! `no_std` support can be enabled by disabling the default `std` feature for
@@ -37,6 +48,15 @@
 //!
 //! Disabling the `std` feature currently only disables the `std::error::Error`
 //! implementation for `ParseError`.
+//!
+//! ## `half` support
+//! Enabling the `half` feature adds [`FPFormat`] implementations for
+//! [`half::f16`](https://docs.rs/half) and `half::bf16`, so literals can be
+//! converted straight to those types with the same `convert` method used for
+//! `f32`/`f64`.
+//! ```toml
+//! hexponent = {version = "0.2", features = ["half"]}
+//! ```
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
@@ -45,22 +65,83 @@ extern crate alloc;
 use alloc::vec::Vec;
 
 use core::fmt;
+use core::fmt::Write as _;
 
 mod fpformat;
 pub use fpformat::FPFormat;
 
+/// Wraps an `f32`/`f64` (or other [`FPFormat`]) to `Display`/`LowerHex` it as
+/// a canonical C99 `%a` hex-float string, e.g. `0x1.5bf0a8p+6`.
+///
+/// ```rust
+/// use hexponent::HexFloat;
+/// assert_eq!(format!("{}", HexFloat(3.25f32)), "0x1.ap+1");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexFloat<F>(
+    /// The wrapped value.
+    pub F,
+);
+
+impl<F: FPFormat> fmt::LowerHex for HexFloat<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_hex(f)
+    }
+}
+
+impl<F: FPFormat> fmt::Display for HexFloat<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// Selects the IEEE-754 rounding rule used when a conversion can't be
+/// represented exactly.
+///
+/// The default used by [`FloatLiteral::convert`] is `NearestTiesToEven`,
+/// which matches what C, Rust and most hardware FPUs do implicitly. The
+/// other variants are useful for matching the semantics of a specific
+/// target or language.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to the nearest representable value; on a tie, round to the
+    /// value whose mantissa is even. This is the IEEE-754 default.
+    NearestTiesToEven,
+    /// Round to the nearest representable value; on a tie, round away from
+    /// zero.
+    NearestTiesToAway,
+    /// Always round toward zero, i.e. truncate.
+    TowardZero,
+    /// Always round toward positive infinity.
+    TowardPositive,
+    /// Always round toward negative infinity.
+    TowardNegative,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 /// Indicates the precision of a conversion
 pub enum ConversionResult<T> {
     /// The conversion was precise and the result represents the original exactly.
     Precise(T),
 
-    // TODO: I should be able to calculate how imprecise the conversion is too,
-    // which might be useful. This also might allow some subnormal numbers to be
-    // returned as precise results.
     /// The conversion was imprecise and the result is as close to the original
     /// as possible.
-    Imprecise(T),
+    Imprecise {
+        /// The value closest to the original.
+        value: T,
+
+        /// How far `value` is from the original, in ULPs of `value`. Positive
+        /// means `value` is larger than the original; negative means it's
+        /// smaller. An overflow to infinity is reported as `+/- INFINITY`,
+        /// since the gap isn't a finite number of ULPs.
+        ///
+        /// The error is computed only from the leading digits that fit in
+        /// the conversion's working register (as many bits as the target
+        /// format is wide); nonzero digits past that affect the rounding
+        /// decision but not this value. A literal smaller than half the
+        /// smallest subnormal always reports an error of magnitude 0.5.
+        ulp_error: f64,
+    },
 }
 
 impl<T> ConversionResult<T> {
@@ -68,7 +149,7 @@ impl<T> ConversionResult<T> {
     pub fn inner(self) -> T {
         match self {
             ConversionResult::Precise(f) => f,
-            ConversionResult::Imprecise(f) => f,
+            ConversionResult::Imprecise { value, .. } => value,
         }
     }
 
@@ -79,10 +160,41 @@ impl<T> ConversionResult<T> {
 
     /// Return whether this result is imprecise.
     pub fn is_imprecise(&self) -> bool {
-        matches!(self, ConversionResult::Imprecise(_))
+        matches!(self, ConversionResult::Imprecise { .. })
     }
 }
 
+/// Error returned by [`FloatLiteral::convert_exact`] when a literal can't be
+/// represented exactly in the requested format.
+///
+/// This covers truncation (extra mantissa bits), overflow (rounding to
+/// infinity) and underflow (rounding to zero) uniformly: all three are just
+/// an [`ConversionResult::Imprecise`] result under the hood.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConversionError<F> {
+    /// The value that the literal would round to, i.e. what
+    /// [`FloatLiteral::convert`] returns for the same literal.
+    pub rounded: F,
+
+    /// How far `rounded` is from the original, in ULPs. See
+    /// [`ConversionResult::Imprecise`].
+    pub ulp_error: f64,
+}
+
+impl<F: fmt::Debug> fmt::Display for ConversionError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "literal cannot be represented exactly, rounds to {:?} ({:+} ulp)",
+            self.rounded, self.ulp_error
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+/// Only available with the `std` feature.
+impl<F: fmt::Debug> std::error::Error for ConversionError<F> {}
+
 /// Error type for parsing hexadecimal literals.
 ///
 /// See the [`ParseErrorKind`](enum.ParseErrorKind.html) documentation for more
@@ -125,6 +237,17 @@ pub enum ParseErrorKind {
     ///
     /// Example: `0x1p3000000000`
     ExponentOverflow,
+    /// An underscore digit separator was found somewhere other than between
+    /// two digits. This can only happen when parsing with
+    /// [`ParseOptions::allow_underscores`] enabled.
+    ///
+    /// Example: `0x_1p1` `0x1__0p1` `0x1p1_`
+    MisplacedUnderscore,
+    /// A [`NumberLiteral::Integer`] value doesn't fit in the width implied by
+    /// its [`IntegerSuffix`] (32 bits when unsuffixed).
+    ///
+    /// Example: `0x100000000` (requires a `u64`/`i64` suffix)
+    IntegerOverflow,
 }
 
 impl ParseErrorKind {
@@ -133,6 +256,79 @@ impl ParseErrorKind {
     }
 }
 
+/// Options controlling how [`FloatLiteral::from_chars_with_options`] parses
+/// its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// The character that separates the integer and fractional digits.
+    pub decimal_sep: char,
+    /// Whether a `_` is allowed between digits, in the mantissa or the
+    /// exponent, as a visual separator (e.g. `0x0.1_7p8`). A leading,
+    /// trailing, doubled, or otherwise misplaced underscore is still a
+    /// [`ParseErrorKind::MisplacedUnderscore`] error.
+    ///
+    /// Disabled by default, to keep the strict behavior of
+    /// [`FloatLiteral::from_chars`] unchanged.
+    pub allow_underscores: bool,
+    /// Whether a trailing C11 (`f`/`F`/`l`/`L`) or WGSL-style (`h`/`H`)
+    /// floating-suffix is recognized after the exponent, e.g. `0x1p4f`. The
+    /// recognized suffix is recorded on the returned [`FloatLiteral`] and can
+    /// be read back with [`FloatLiteral::suffix`].
+    ///
+    /// Disabled by default, to keep the strict behavior of
+    /// [`FloatLiteral::from_chars`] unchanged.
+    pub allow_suffix: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            decimal_sep: '.',
+            allow_underscores: false,
+            allow_suffix: false,
+        }
+    }
+}
+
+/// A C11 (`f`/`F`/`l`/`L`) or WGSL-style (`h`/`H`) floating-suffix, recorded
+/// on a [`FloatLiteral`] when parsed with [`ParseOptions::allow_suffix`].
+///
+/// See [`FloatLiteral::convert_to_suffixed`] for using this to pick (or
+/// validate) the conversion target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatSuffix {
+    /// No floating-suffix was present.
+    Unsuffixed,
+    /// The `f`/`F` suffix, indicating single precision.
+    F32,
+    /// The `l`/`L` suffix, indicating double precision.
+    F64,
+    /// The WGSL-style `h`/`H` suffix, indicating half precision.
+    F16,
+}
+
+/// Error returned by [`FloatLiteral::convert_to_suffixed`] when the
+/// requested format doesn't match the literal's embedded floating-suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuffixMismatch {
+    /// The suffix embedded in the literal.
+    pub suffix: FloatSuffix,
+}
+
+impl fmt::Display for SuffixMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "literal has a {:?} suffix that doesn't match the requested type",
+            self.suffix
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+/// Only available with the `std` feature.
+impl std::error::Error for SuffixMismatch {}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind {
@@ -140,6 +336,10 @@ impl fmt::Display for ParseError {
             ParseErrorKind::MissingDigits => write!(f, "literal must have digits"),
             ParseErrorKind::MissingExponent => write!(f, "exponent not present"),
             ParseErrorKind::ExponentOverflow => write!(f, "exponent too large to fit in integer"),
+            ParseErrorKind::MisplacedUnderscore => write!(f, "misplaced digit separator"),
+            ParseErrorKind::IntegerOverflow => {
+                write!(f, "integer too large to fit in the requested width")
+            }
         }
     }
 }
@@ -159,6 +359,18 @@ where
     consumed: usize,
 }
 
+impl<Chars> Clone for CharsIterator<Chars>
+where
+    Chars: Iterator<Item = char> + Clone,
+{
+    fn clone(&self) -> Self {
+        CharsIterator {
+            chars: self.chars.clone(),
+            consumed: self.consumed,
+        }
+    }
+}
+
 impl<Chars> CharsIterator<Chars>
 where
     Chars: Iterator<Item = char>,
@@ -182,15 +394,173 @@ where
         res
     }
 
+    /// Consume a run of characters matched by `is_digit`, optionally allowing
+    /// `_` between digits as a visual separator. A leading, trailing,
+    /// doubled, or otherwise misplaced underscore is a
+    /// [`ParseErrorKind::MisplacedUnderscore`] error.
+    fn consume_digit_run(
+        &mut self,
+        allow_underscores: bool,
+        is_digit: impl Fn(char) -> bool,
+    ) -> Result<String, ParseError> {
+        let mut run = String::new();
+        let mut prev_was_digit = false;
+        loop {
+            match self.peek() {
+                Some(c) if is_digit(c) => {
+                    run.push(c);
+                    self.next();
+                    prev_was_digit = true;
+                }
+                Some('_') if allow_underscores => {
+                    let underscore_index = self.consumed;
+                    if !prev_was_digit {
+                        return Err(ParseErrorKind::MisplacedUnderscore.at(underscore_index));
+                    }
+                    self.next();
+                    if !self.peek().is_some_and(&is_digit) {
+                        return Err(ParseErrorKind::MisplacedUnderscore.at(underscore_index));
+                    }
+                    prev_was_digit = false;
+                }
+                _ => break,
+            }
+        }
+        Ok(run)
+    }
+
+    /// Consume a sequence of hex digits and return it as a sequence of u8s.
+    /// The returned values are integers, not ascii characters.
+    fn consume_hex_digits(&mut self, allow_underscores: bool) -> Result<Vec<u8>, ParseError> {
+        let run = self.consume_digit_run(allow_underscores, |c| c.is_ascii_hexdigit())?;
+        Ok(run.chars().map(|c| c.to_digit(16).unwrap() as u8).collect())
+    }
+}
+
+/// Get the value of an ASCII hex digit byte, branching on the byte range
+/// directly rather than round-tripping through `char`.
+fn hex_digit_value(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b - b'a' + 10,
+        b'A'..=b'F' => b - b'A' + 10,
+        _ => unreachable!("consume_digit_run only yields bytes matched by is_ascii_hexdigit"),
+    }
+}
+
+/// An iterator that counts the number of bytes consumed, operating directly
+/// on ASCII bytes rather than decoding UTF-8 chars. Mirrors `CharsIterator`,
+/// but since hex float literals are pure ASCII, non-ASCII or invalid-UTF-8
+/// bytes simply fail to match and end the literal, exactly as a non-literal
+/// char would with `CharsIterator`.
+struct BytesIterator<'a> {
+    bytes: &'a [u8],
+    consumed: usize,
+}
+
+impl<'a> BytesIterator<'a> {
+    /// Get the current byte, or \0.
+    fn current(&self) -> u8 {
+        self.peek().unwrap_or(0)
+    }
+
+    /// Get the current byte, or None.
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.consumed).copied()
+    }
+
+    /// Get the next byte, incrementing self.consumed.
+    fn next(&mut self) -> Option<u8> {
+        let res = self.peek();
+        if res.is_some() {
+            self.consumed += 1;
+        }
+        res
+    }
+
+    /// Consume a run of bytes matched by `is_digit`, optionally allowing `_`
+    /// between digits as a visual separator. A leading, trailing, doubled,
+    /// or otherwise misplaced underscore is a
+    /// [`ParseErrorKind::MisplacedUnderscore`] error.
+    fn consume_digit_run(
+        &mut self,
+        allow_underscores: bool,
+        is_digit: impl Fn(u8) -> bool,
+    ) -> Result<Vec<u8>, ParseError> {
+        let mut run = Vec::new();
+        let mut prev_was_digit = false;
+        loop {
+            match self.peek() {
+                Some(b) if is_digit(b) => {
+                    run.push(b);
+                    self.next();
+                    prev_was_digit = true;
+                }
+                Some(b'_') if allow_underscores => {
+                    let underscore_index = self.consumed;
+                    if !prev_was_digit {
+                        return Err(ParseErrorKind::MisplacedUnderscore.at(underscore_index));
+                    }
+                    self.next();
+                    if !self.peek().is_some_and(&is_digit) {
+                        return Err(ParseErrorKind::MisplacedUnderscore.at(underscore_index));
+                    }
+                    prev_was_digit = false;
+                }
+                _ => break,
+            }
+        }
+        Ok(run)
+    }
+
     /// Consume a sequence of hex digits and return it as a sequence of u8s.
     /// The returned values are integers, not ascii characters.
-    fn consume_hex_digits(&mut self) -> Vec<u8> {
-        let mut digits = Vec::new();
-        while let Some(digit) = self.peek().and_then(|c| c.to_digit(16)) {
-            digits.push(digit as u8);
-            self.next();
+    fn consume_hex_digits(&mut self, allow_underscores: bool) -> Result<Vec<u8>, ParseError> {
+        let run = self.consume_digit_run(allow_underscores, |b| b.is_ascii_hexdigit())?;
+        Ok(run.into_iter().map(hex_digit_value).collect())
+    }
+
+    /// Consume a run of decimal digits, accumulating directly into an `i32`
+    /// rather than building an intermediate string, optionally allowing `_`
+    /// between digits as in [`BytesIterator::consume_digit_run`]. Returns
+    /// `None` if there are no digits to consume.
+    fn consume_decimal_i32(
+        &mut self,
+        is_negative: bool,
+        allow_underscores: bool,
+    ) -> Result<Option<i32>, ParseError> {
+        let mut value: i32 = 0;
+        let mut any_digits = false;
+        let mut prev_was_digit = false;
+        loop {
+            match self.peek() {
+                Some(b) if b.is_ascii_digit() => {
+                    self.next();
+                    any_digits = true;
+                    prev_was_digit = true;
+                    let digit = i32::from(b - b'0');
+                    let digit = if is_negative { -digit } else { digit };
+                    value = value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(digit))
+                        .ok_or(())
+                        .map_err(|()| ParseErrorKind::ExponentOverflow.at(self.consumed))?;
+                }
+                Some(b'_') if allow_underscores => {
+                    let underscore_index = self.consumed;
+                    if !prev_was_digit {
+                        return Err(ParseErrorKind::MisplacedUnderscore.at(underscore_index));
+                    }
+                    self.next();
+                    if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                        return Err(ParseErrorKind::MisplacedUnderscore.at(underscore_index));
+                    }
+                    prev_was_digit = false;
+                }
+                _ => break,
+            }
         }
-        digits
+        Ok(if any_digits { Some(value) } else { None })
     }
 }
 
@@ -208,15 +578,42 @@ pub struct FloatLiteral {
     digits: Vec<u8>,
     decimal_offset: i32,
     exponent: i32,
+    suffix: FloatSuffix,
 }
 
 impl FloatLiteral {
     /// Convert the `self` to an `f32` or `f64` and return the precision of the
     /// conversion.
+    ///
+    /// This always rounds to nearest, ties to even. Use
+    /// [`convert_with_rounding`](FloatLiteral::convert_with_rounding) to pick
+    /// a different [`RoundingMode`].
     pub fn convert<F: FPFormat>(self) -> ConversionResult<F> {
         F::from_literal(self)
     }
 
+    /// Convert `self` using the given [`RoundingMode`] instead of the default
+    /// round-to-nearest-ties-to-even.
+    pub fn convert_with_rounding<F: FPFormat>(self, rounding: RoundingMode) -> ConversionResult<F> {
+        F::from_literal_with_rounding(self, rounding)
+    }
+
+    /// Convert `self` to `F`, requiring the result to be exact.
+    ///
+    /// Returns `Ok` when [`convert`](FloatLiteral::convert) would have
+    /// returned `Precise`, and `Err` (carrying the rounded value) otherwise.
+    /// This is useful for assemblers or constant-folders that want to reject
+    /// a literal rather than silently lose precision.
+    pub fn convert_exact<F: FPFormat>(self) -> Result<F, ConversionError<F>> {
+        match self.convert() {
+            ConversionResult::Precise(value) => Ok(value),
+            ConversionResult::Imprecise {
+                value: rounded,
+                ulp_error,
+            } => Err(ConversionError { rounded, ulp_error }),
+        }
+    }
+
     /// Helper used by the tests.
     #[cfg(test)]
     pub fn create(is_positive: bool, digits: Vec<u8>, decimal_offset: i32, exponent: i32) -> Self {
@@ -225,9 +622,46 @@ impl FloatLiteral {
             digits,
             decimal_offset,
             exponent,
+            suffix: FloatSuffix::Unsuffixed,
         }
     }
 
+    /// The C11 or WGSL-style floating-suffix parsed from the literal, or
+    /// [`FloatSuffix::Unsuffixed`] if none was present (including when
+    /// parsed without [`ParseOptions::allow_suffix`]).
+    pub fn suffix(&self) -> FloatSuffix {
+        self.suffix
+    }
+
+    /// Convert `self` to `F`, honoring its embedded floating-suffix.
+    ///
+    /// Returns [`SuffixMismatch`] if the literal has a suffix and it doesn't
+    /// match `F` (as reported by [`FPFormat::suffix`]). A literal with no
+    /// suffix, or `F` with no suffix of its own (e.g. `half::bf16`), is
+    /// never a mismatch.
+    pub fn convert_to_suffixed<F: FPFormat>(self) -> Result<ConversionResult<F>, SuffixMismatch> {
+        if self.suffix != FloatSuffix::Unsuffixed && Some(self.suffix) != F::suffix() {
+            return Err(SuffixMismatch {
+                suffix: self.suffix,
+            });
+        }
+        Ok(self.convert())
+    }
+
+    /// Render `self` as the same canonical, round-trippable hex-float string
+    /// produced by this type's `Display`/`LowerHex` impls (e.g. `"0x1.4p+4"`
+    /// for `"0x0014.0"`), as an owned `String`.
+    ///
+    /// This is a convenience for callers that want the normalized form
+    /// without going through `format!` themselves, e.g. for diffing or
+    /// verifying that a parse preserved the value.
+    pub fn to_normalized_string(&self) -> String {
+        let mut out = String::new();
+        // Writing to a `String` never fails.
+        write!(out, "{}", self).unwrap();
+        out
+    }
+
     /// Parse a sequence of chars into a `FloatLiteral`.
     ///
     /// This is based on hexadecimal floating constants in the C11 specification,
@@ -240,6 +674,29 @@ impl FloatLiteral {
     where
         Chars: Iterator<Item = char> + Clone,
     {
+        FloatLiteral::from_chars_with_options(
+            input,
+            ParseOptions {
+                decimal_sep,
+                allow_underscores: false,
+                allow_suffix: false,
+            },
+            out_consumed,
+        )
+    }
+
+    /// Parse a sequence of chars into a `FloatLiteral`, with additional
+    /// parsing behavior beyond the decimal separator. See [`ParseOptions`]
+    /// for what's configurable.
+    pub fn from_chars_with_options<Chars>(
+        input: Chars,
+        options: ParseOptions,
+        out_consumed: &mut usize,
+    ) -> Result<FloatLiteral, ParseError>
+    where
+        Chars: Iterator<Item = char> + Clone,
+    {
+        let decimal_sep = options.decimal_sep;
         let mut data = CharsIterator {
             chars: input.fuse().peekable(),
             consumed: 0,
@@ -268,12 +725,12 @@ impl FloatLiteral {
         }
         data.next();
 
-        let ipart: Vec<u8> = data.consume_hex_digits();
+        let ipart: Vec<u8> = data.consume_hex_digits(options.allow_underscores)?;
         let ipart_len = ipart.len();
 
         let fpart: Vec<u8> = if data.current() == decimal_sep {
             data.next();
-            data.consume_hex_digits()
+            data.consume_hex_digits(options.allow_underscores)?
         } else {
             Vec::new()
         };
@@ -301,9 +758,9 @@ impl FloatLiteral {
             };
 
             // Collect the exponent into a string, optionally with a sign, and then use Rust's parsing.
-            while data.current().is_ascii_digit() {
-                exponent_str.push(data.next().unwrap());
-            }
+            let exponent_digits =
+                data.consume_digit_run(options.allow_underscores, |c| c.is_ascii_digit())?;
+            exponent_str.push_str(&exponent_digits);
 
             if exponent_str.is_empty() || exponent_str == "-" {
                 return Err(ParseErrorKind::MissingExponent.at(exponent_start));
@@ -314,6 +771,26 @@ impl FloatLiteral {
                 .map_err(|_| ParseErrorKind::ExponentOverflow.at(exponent_start))?;
         }
 
+        let suffix = if options.allow_suffix {
+            match data.current() {
+                'f' | 'F' => {
+                    data.next();
+                    FloatSuffix::F32
+                }
+                'l' | 'L' => {
+                    data.next();
+                    FloatSuffix::F64
+                }
+                'h' | 'H' => {
+                    data.next();
+                    FloatSuffix::F16
+                }
+                _ => FloatSuffix::Unsuffixed,
+            }
+        } else {
+            FloatSuffix::Unsuffixed
+        };
+
         let mut raw_digits = ipart;
         raw_digits.extend_from_slice(&fpart);
 
@@ -339,6 +816,159 @@ impl FloatLiteral {
             digits,
             decimal_offset,
             exponent,
+            suffix,
+        })
+    }
+
+    /// Parse a sequence of bytes into a `FloatLiteral`, without decoding it
+    /// as UTF-8 first.
+    ///
+    /// This is a byte-oriented fast path for the same grammar as
+    /// [`FloatLiteral::from_chars`]: since hex float literals are pure
+    /// ASCII, the bytes can be classified directly, and any trailing bytes
+    /// that aren't valid UTF-8 simply end the literal, just as any other
+    /// non-matching byte would.
+    pub fn from_bytes(
+        input: &[u8],
+        decimal_sep: u8,
+        out_consumed: &mut usize,
+    ) -> Result<FloatLiteral, ParseError> {
+        FloatLiteral::from_bytes_with_options(
+            input,
+            ParseOptions {
+                decimal_sep: decimal_sep as char,
+                allow_underscores: false,
+                allow_suffix: false,
+            },
+            out_consumed,
+        )
+    }
+
+    /// Parse a sequence of bytes into a `FloatLiteral`, with additional
+    /// parsing behavior beyond the decimal separator. See [`ParseOptions`]
+    /// for what's configurable.
+    ///
+    /// [`ParseOptions::decimal_sep`] is truncated to its low byte, so it
+    /// must be an ASCII character.
+    pub fn from_bytes_with_options(
+        input: &[u8],
+        options: ParseOptions,
+        out_consumed: &mut usize,
+    ) -> Result<FloatLiteral, ParseError> {
+        let decimal_sep = options.decimal_sep as u8;
+        let mut data = BytesIterator {
+            bytes: input,
+            consumed: 0,
+        };
+
+        let is_positive = match data.peek() {
+            Some(b'+') => {
+                data.next();
+                true
+            }
+            Some(b'-') => {
+                data.next();
+                false
+            }
+            _ => true,
+        };
+
+        // Parse 0x or 0X prefix.
+        let prefix_start = data.consumed;
+        if data.current() != b'0' {
+            return Err(ParseErrorKind::MissingPrefix.at(prefix_start));
+        }
+        data.next();
+        if data.current() != b'x' && data.current() != b'X' {
+            return Err(ParseErrorKind::MissingPrefix.at(prefix_start));
+        }
+        data.next();
+
+        let ipart: Vec<u8> = data.consume_hex_digits(options.allow_underscores)?;
+        let ipart_len = ipart.len();
+
+        let fpart: Vec<u8> = if data.current() == decimal_sep {
+            data.next();
+            data.consume_hex_digits(options.allow_underscores)?
+        } else {
+            Vec::new()
+        };
+
+        // Must have digits before or after the decimal point.
+        if fpart.is_empty() && ipart.is_empty() {
+            return Err(ParseErrorKind::MissingDigits.at(data.consumed));
+        }
+
+        let mut exponent = 0;
+        if data.current() == b'p' || data.current() == b'P' {
+            data.next();
+
+            let exponent_start = data.consumed;
+            let is_negative = match data.current() {
+                b'+' => {
+                    data.next();
+                    false
+                }
+                b'-' => {
+                    data.next();
+                    true
+                }
+                _ => false,
+            };
+
+            // Accumulate the exponent straight into an `i32`, without an
+            // intermediate string allocation.
+            exponent = data
+                .consume_decimal_i32(is_negative, options.allow_underscores)?
+                .ok_or_else(|| ParseErrorKind::MissingExponent.at(exponent_start))?;
+        }
+
+        let suffix = if options.allow_suffix {
+            match data.current() {
+                b'f' | b'F' => {
+                    data.next();
+                    FloatSuffix::F32
+                }
+                b'l' | b'L' => {
+                    data.next();
+                    FloatSuffix::F64
+                }
+                b'h' | b'H' => {
+                    data.next();
+                    FloatSuffix::F16
+                }
+                _ => FloatSuffix::Unsuffixed,
+            }
+        } else {
+            FloatSuffix::Unsuffixed
+        };
+
+        let mut raw_digits = ipart;
+        raw_digits.extend_from_slice(&fpart);
+
+        let first_digit = raw_digits.iter().position(|&d| d != 0);
+        let (digits, decimal_offset) = if let Some(first_digit) = first_digit {
+            // Unwrap is safe because there is at least one digit.
+            let last_digit = raw_digits.iter().rposition(|&d| d != 0).unwrap();
+            let decimal_offset = (ipart_len as i32) - (first_digit as i32);
+
+            // Trim off the leading zeros
+            raw_digits.truncate(last_digit + 1);
+            // Trim off the trailing zeros
+            raw_digits.drain(..first_digit);
+
+            (raw_digits, decimal_offset)
+        } else {
+            (Vec::new(), 0)
+        };
+
+        *out_consumed = data.consumed;
+        Ok(FloatLiteral {
+            is_positive,
+            digits,
+            decimal_offset,
+            exponent,
+            suffix,
         })
     }
 }
@@ -356,6 +986,204 @@ where
     FloatLiteral::from_chars(input, decimal_sep, out_consumed).map(|f| f.convert().inner())
 }
 
+/// The C-style `u`/`i` integer-width suffix on a [`NumberLiteral::Integer`],
+/// e.g. `u32` in `0xFFu32`.
+///
+/// The bare `u`/`i` forms (with no `32`/`64` following) are equivalent to
+/// `u32`/`i32`. [`Unsuffixed`](IntegerSuffix::Unsuffixed) literals are also
+/// checked against 32 bits, the same as `U32`, but are stored without an
+/// explicit type so the caller can apply its own default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerSuffix {
+    /// No integer suffix was present. Checked against 32 bits, like `U32`.
+    Unsuffixed,
+    /// The `i`/`i32` suffix.
+    I32,
+    /// The `u`/`u32` suffix.
+    U32,
+    /// The `i64` suffix.
+    I64,
+    /// The `u64` suffix.
+    U64,
+}
+
+impl IntegerSuffix {
+    /// The number of bits a literal with this suffix is checked against.
+    fn width(self) -> u32 {
+        match self {
+            IntegerSuffix::Unsuffixed | IntegerSuffix::I32 | IntegerSuffix::U32 => 32,
+            IntegerSuffix::I64 | IntegerSuffix::U64 => 64,
+        }
+    }
+}
+
+/// A parsed hex integer literal, as produced by [`parse_number_literal`] when
+/// the input has no decimal point or `p`/`P` exponent.
+///
+/// Hex integer literals are bit patterns: the magnitude is checked against
+/// the full unsigned range of the suffix's width (e.g. up to `0xFFFFFFFF`
+/// for a 32-bit suffix), regardless of whether the suffix is signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexInteger {
+    is_positive: bool,
+    value: u64,
+    suffix: IntegerSuffix,
+}
+
+impl HexInteger {
+    /// Whether the literal had a leading `-`.
+    pub fn is_positive(&self) -> bool {
+        self.is_positive
+    }
+
+    /// The magnitude of the literal, as an unsigned bit pattern.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// The integer-width suffix the literal was parsed with.
+    pub fn suffix(&self) -> IntegerSuffix {
+        self.suffix
+    }
+}
+
+/// A hex numeric literal, either a [`FloatLiteral`] or a [`HexInteger`], as
+/// produced by [`parse_number_literal`].
+///
+/// This lets a single `0x…` lexer rule serve both floats and integers, the
+/// way C, WGSL and similar hex-literal grammars do: the literal is only
+/// recognized as a float once a decimal point or exponent is seen, and is an
+/// integer otherwise.
+#[derive(Debug, Clone)]
+pub enum NumberLiteral {
+    /// A literal with a decimal point or `p`/`P` exponent, e.g. `0x1.8p3`.
+    Float(FloatLiteral),
+    /// A literal with neither, e.g. `0xFF` or `0xFFu64`.
+    Integer(HexInteger),
+}
+
+/// Parse a hex numeric literal: a [`FloatLiteral`] if a decimal point or
+/// `p`/`P` exponent is present, or a [`HexInteger`] otherwise. See
+/// [`NumberLiteral`].
+///
+/// [`ParseOptions::allow_suffix`] is ignored for integers, which always
+/// recognize a trailing `u`/`i` (optionally followed by `32`/`64`) width
+/// suffix; see [`IntegerSuffix`].
+pub fn parse_number_literal<Chars>(
+    input: Chars,
+    options: ParseOptions,
+    out_consumed: &mut usize,
+) -> Result<NumberLiteral, ParseError>
+where
+    Chars: Iterator<Item = char> + Clone,
+{
+    let decimal_sep = options.decimal_sep;
+    let float_input = input.clone();
+    let mut data = CharsIterator {
+        chars: input.fuse().peekable(),
+        consumed: 0,
+    };
+
+    let is_positive = match data.peek() {
+        Some('+') => {
+            data.next();
+            true
+        }
+        Some('-') => {
+            data.next();
+            false
+        }
+        _ => true,
+    };
+
+    // Parse 0x or 0X prefix.
+    let prefix_start = data.consumed;
+    if data.current() != '0' {
+        return Err(ParseErrorKind::MissingPrefix.at(prefix_start));
+    }
+    data.next();
+    if data.current() != 'x' && data.current() != 'X' {
+        return Err(ParseErrorKind::MissingPrefix.at(prefix_start));
+    }
+    data.next();
+
+    let digits_start = data.consumed;
+    let ipart = data.consume_hex_digits(options.allow_underscores)?;
+
+    // A decimal point or exponent means this is actually a float; hand the
+    // untouched input back to the full float parser.
+    if data.current() == decimal_sep || data.current() == 'p' || data.current() == 'P' {
+        let literal = FloatLiteral::from_chars_with_options(float_input, options, out_consumed)?;
+        return Ok(NumberLiteral::Float(literal));
+    }
+
+    if ipart.is_empty() {
+        return Err(ParseErrorKind::MissingDigits.at(data.consumed));
+    }
+
+    let suffix = match data.current() {
+        'u' | 'U' => {
+            data.next();
+            parse_integer_width(&mut data, IntegerSuffix::U32, IntegerSuffix::U64)
+        }
+        'i' | 'I' => {
+            data.next();
+            parse_integer_width(&mut data, IntegerSuffix::I32, IntegerSuffix::I64)
+        }
+        _ => IntegerSuffix::Unsuffixed,
+    };
+
+    // Checking after every digit keeps `value` far below `u128::MAX`, so
+    // arbitrarily long digit runs report an overflow instead of wrapping.
+    let max_value: u128 = (1u128 << suffix.width()) - 1;
+    let mut value: u128 = 0;
+    for digit in ipart {
+        value = value * 16 + u128::from(digit);
+        if value > max_value {
+            return Err(ParseErrorKind::IntegerOverflow.at(digits_start));
+        }
+    }
+
+    *out_consumed = data.consumed;
+    Ok(NumberLiteral::Integer(HexInteger {
+        is_positive,
+        value: value as u64,
+        suffix,
+    }))
+}
+
+/// Consume an optional `32`/`64` width following a `u`/`i` integer suffix.
+fn parse_integer_width<Chars>(
+    data: &mut CharsIterator<Chars>,
+    width_32: IntegerSuffix,
+    width_64: IntegerSuffix,
+) -> IntegerSuffix
+where
+    Chars: Iterator<Item = char> + Clone,
+{
+    let mut lookahead = data.clone();
+    match lookahead.current() {
+        '3' => {
+            lookahead.next();
+            if lookahead.current() == '2' {
+                lookahead.next();
+                *data = lookahead;
+                return width_32;
+            }
+        }
+        '6' => {
+            lookahead.next();
+            if lookahead.current() == '4' {
+                lookahead.next();
+                *data = lookahead;
+                return width_64;
+            }
+        }
+        _ => {}
+    }
+    width_32
+}
+
 impl core::str::FromStr for FloatLiteral {
     type Err = ParseError;
     fn from_str(s: &str) -> Result<FloatLiteral, ParseError> {
@@ -375,5 +1203,41 @@ impl From<FloatLiteral> for f64 {
     }
 }
 
+impl fmt::LowerHex for FloatLiteral {
+    /// Reconstructs a canonical hex float from the parsed digits, with the
+    /// leading digit before the point and no trailing zero digits, e.g.
+    /// `"0x1.4p+4"` for `"0x0014.0"`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_positive {
+            write!(f, "-")?;
+        }
+
+        let first_digit = match self.digits.first() {
+            Some(&digit) => digit,
+            None => return write!(f, "0x0p+0"),
+        };
+
+        // Each hex digit is worth 4 bits; `decimal_offset` counts how many
+        // digits sit before the point, so moving the point to just after
+        // the (single) leading digit shifts the exponent by that much.
+        let binary_exponent = 4 * (self.decimal_offset - 1) + self.exponent;
+
+        write!(f, "0x{:x}", first_digit)?;
+        if self.digits.len() > 1 {
+            write!(f, ".")?;
+            for &digit in &self.digits[1..] {
+                write!(f, "{:x}", digit)?;
+            }
+        }
+        write!(f, "p{:+}", binary_exponent)
+    }
+}
+
+impl fmt::Display for FloatLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
 #[cfg(test)]
 mod tests;
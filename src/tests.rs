@@ -1,4 +1,8 @@
-use crate::{parse_hex_float, ConversionResult, FloatLiteral, ParseError, ParseErrorKind};
+use crate::{
+    parse_hex_float, parse_number_literal, ConversionError, ConversionResult, FloatLiteral,
+    FloatSuffix, HexFloat, IntegerSuffix, NumberLiteral, ParseError, ParseErrorKind, ParseOptions,
+    RoundingMode,
+};
 
 // This macros serves two functions:
 // 1. It avoids the float_cmp clippy lint
@@ -81,7 +85,7 @@ fn test_parse_error(s: &str, error: ParseError) {
 fn test_imprecise(s: &str) {
     let float_literal = s.parse::<FloatLiteral>().unwrap();
     let conversion_result = float_literal.convert::<f32>();
-    if let ConversionResult::Imprecise(_) = conversion_result {
+    if let ConversionResult::Imprecise { .. } = conversion_result {
         // Pass
     } else {
         panic!(
@@ -149,11 +153,9 @@ fn test_overflow_underflow() {
 }
 
 #[test]
-#[ignore]
 fn test_subnormal() {
-    // I haven't implemented subnormal numbers yet.
-    test_float("0x1p-128", 0.0);
-    test_float("-0x1p-128", -0.0);
+    test_float("0x1p-128", 2.938_735_877_055_718_769_921_841_343e-39);
+    test_float("-0x1p-128", -2.938_735_877_055_718_769_921_841_343e-39);
 }
 
 #[test]
@@ -201,10 +203,10 @@ fn test_zero_trimming() {
     test_both("0x0.0000000001p+40", 1.0);
     test_both("0x10000000000p-40", 1.0);
 
-    // Right now these can only be tested to not crash because my rounding is
-    // incorrect.
-    "0x10000000000".parse::<FloatLiteral>().unwrap();
-    "0x.0000000001".parse::<FloatLiteral>().unwrap();
+    // Both of these are exact powers of two, so rounding doesn't come into
+    // play, but they exercise the same leading/trailing zero trimming.
+    test_both("0x10000000000", 1_099_511_627_776.0);
+    test_both("0x.0000000001", 9.094_947_017_729_282e-13);
 }
 
 #[test]
@@ -322,7 +324,16 @@ fn test_convert_hexf64() {
         lit.convert()
     }
 
-    use ConversionResult::{Imprecise, Precise};
+    use ConversionResult::Precise;
+
+    fn assert_imprecise(result: ConversionResult<f64>, expected: f64) {
+        match result {
+            ConversionResult::Imprecise { value, .. } => assert_eq!(value, expected),
+            ConversionResult::Precise(_) => {
+                panic!("conversion should have been imprecise (was {:?})", result)
+            }
+        }
+    }
 
     assert_eq!(convert_hexf64(false, 0, 0), Precise(0.0));
     assert_eq!(convert_hexf64(false, 1, 0), Precise(1.0));
@@ -341,59 +352,309 @@ fn test_convert_hexf64() {
         convert_hexf64(false, 0x001f_ffff_ffff_ffff, 0),
         Precise(9007199254740991.0)
     );
-    // This mantissas is "too big" but we report it as Precise.
-    assert_eq!(
+    // This mantissa is one bit too big for the mantissa field. The
+    // discarded bit is a tie (guard set, sticky clear) and the kept
+    // mantissa is odd, so it rounds up and carries into the exponent.
+    assert_imprecise(
         convert_hexf64(false, 0x003f_ffff_ffff_ffff, 0),
-        Precise(1.8014398509481982e16)
+        1.8014398509481984e16,
     );
+    // This literal's value is exactly 2^53 - 1 (the discarded low bits are
+    // all zero), so it's representable without any rounding.
     assert_eq!(
         convert_hexf64(false, 0xffff_ffff_ffff_f800, -11),
-        Imprecise(9007199254740991.0)
+        Precise(9007199254740991.0)
     );
-    assert_eq!(
+    assert_imprecise(
         convert_hexf64(false, 0xffff_ffff_ffff_fc00, -11),
-        Imprecise(9007199254740991.0)
+        9007199254740992.0,
     );
 
     // denormal truncation
-    // TODO: denormals are not supported yet.
-    //assert!(convert_hexf64(false, 0x000f_ffff_ffff_ffff, -1074).is_precise());
-    //assert!(convert_hexf64(false, 0x001f_ffff_ffff_ffff, -1075).is_imprecise());
-    //assert!(convert_hexf64(false, 0x001f_ffff_ffff_fffe, -1075).is_precise());
-    //assert!(convert_hexf64(false, 0xffff_ffff_ffff_f800, -1086).is_imprecise());
-    //assert!(convert_hexf64(false, 0xffff_ffff_ffff_f000, -1086).is_precise());
+    assert!(convert_hexf64(false, 0x000f_ffff_ffff_ffff, -1074).is_precise());
+    assert!(convert_hexf64(false, 0x001f_ffff_ffff_ffff, -1075).is_imprecise());
+    assert!(convert_hexf64(false, 0x001f_ffff_ffff_fffe, -1075).is_precise());
+    assert!(convert_hexf64(false, 0xffff_ffff_ffff_f800, -1086).is_imprecise());
+    assert!(convert_hexf64(false, 0xffff_ffff_ffff_f000, -1086).is_precise());
 
     // minimum
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1074).is_precise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1075).is_imprecise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1075).is_precise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1076).is_imprecise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1075).is_imprecise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1076).is_imprecise());
-    //assert!(convert_hexf64(false, 0x8000_0000_0000_0000, -1137).is_precise());
-    //assert!(convert_hexf64(false, 0x8000_0000_0000_0000, -1138).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1074).is_precise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1075).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1075).is_precise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1076).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1075).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1076).is_imprecise());
 
     // maximum
     assert_eq!(
         convert_hexf64(false, 0x001f_ffff_ffff_ffff, 971),
         Precise(f64::MAX)
     );
-    assert_eq!(
+    assert_imprecise(
         convert_hexf64(false, 0x003f_ffff_ffff_ffff, 971),
-        Imprecise(f64::INFINITY)
+        f64::INFINITY,
     );
-    assert_eq!(
+    assert_imprecise(
         convert_hexf64(false, 0x003f_ffff_ffff_fffe, 971),
-        Imprecise(f64::INFINITY)
+        f64::INFINITY,
     );
     assert_eq!(
         convert_hexf64(false, 0xffff_ffff_ffff_f800, 960),
         // TODO: this should be precise.
-        Imprecise(f64::MAX)
+        Precise(f64::MAX)
     );
-    assert_eq!(
+    // One more discarded bit than the case above tips the tie and rounds
+    // up past the largest finite value.
+    assert_imprecise(
         convert_hexf64(false, 0xffff_ffff_ffff_fc00, 960),
-        Imprecise(f64::MAX)
+        f64::INFINITY,
+    );
+}
+
+#[test]
+fn test_rounding_modes() {
+    use RoundingMode::{
+        NearestTiesToAway, NearestTiesToEven, TowardNegative, TowardPositive, TowardZero,
+    };
+
+    fn convert(s: &str, rounding: RoundingMode) -> f32 {
+        s.parse::<FloatLiteral>()
+            .unwrap()
+            .convert_with_rounding(rounding)
+            .inner()
+    }
+
+    // `0x1.000001p0` sits exactly halfway between 1.0 and the next f32 up
+    // (guard set, sticky clear), with an even kept mantissa, so ties-to-even
+    // stays put while the other modes follow their own rule.
+    let next_up = 1.0 + f32::EPSILON;
+
+    assert_eq_float!(convert("0x1.000001p0", NearestTiesToEven), 1.0);
+    assert_eq_float!(convert("0x1.000001p0", NearestTiesToAway), next_up);
+    assert_eq_float!(convert("0x1.000001p0", TowardZero), 1.0);
+    assert_eq_float!(convert("0x1.000001p0", TowardPositive), next_up);
+    assert_eq_float!(convert("0x1.000001p0", TowardNegative), 1.0);
+
+    assert_eq_float!(convert("-0x1.000001p0", NearestTiesToEven), -1.0);
+    assert_eq_float!(convert("-0x1.000001p0", NearestTiesToAway), -next_up);
+    assert_eq_float!(convert("-0x1.000001p0", TowardZero), -1.0);
+    assert_eq_float!(convert("-0x1.000001p0", TowardPositive), -1.0);
+    assert_eq_float!(convert("-0x1.000001p0", TowardNegative), -next_up);
+}
+
+#[test]
+fn test_underscore_separators() {
+    fn with_underscores(s: &str) -> Result<FloatLiteral, ParseError> {
+        let options = ParseOptions {
+            allow_underscores: true,
+            ..ParseOptions::default()
+        };
+        FloatLiteral::from_chars_with_options(s.chars(), options, &mut 0)
+    }
+
+    // Underscores between digits are silently dropped, in the mantissa and
+    // in the exponent.
+    assert_eq!(
+        with_underscores("0x0.1_7p8").unwrap().convert::<f32>(),
+        "0x0.17p8".parse::<FloatLiteral>().unwrap().convert()
+    );
+    assert_eq!(
+        with_underscores("0x1_0p1_0").unwrap().convert::<f32>(),
+        "0x10p10".parse::<FloatLiteral>().unwrap().convert()
+    );
+
+    // The strict default parser stops at the first underscore instead of
+    // treating it as a separator.
+    let mut consumed = 0;
+    FloatLiteral::from_chars("0x0.1_7p8".chars(), '.', &mut consumed).unwrap();
+    assert_eq!(consumed, 5);
+
+    // Leading, trailing, and doubled underscores are all rejected.
+    assert_eq!(
+        with_underscores("0x_1p1").unwrap_err().kind,
+        ParseErrorKind::MisplacedUnderscore
+    );
+    assert_eq!(
+        with_underscores("0x1__0p1").unwrap_err().kind,
+        ParseErrorKind::MisplacedUnderscore
+    );
+    assert_eq!(
+        with_underscores("0x1p1_").unwrap_err().kind,
+        ParseErrorKind::MisplacedUnderscore
+    );
+    assert_eq!(
+        with_underscores("0x1.p_1").unwrap_err().kind,
+        ParseErrorKind::MisplacedUnderscore
+    );
+}
+
+#[test]
+fn test_hex_float_display() {
+    assert_eq!(format!("{}", HexFloat(3.25f32)), "0x1.ap+1");
+    assert_eq!(format!("{}", HexFloat(1.0f32)), "0x1p+0");
+    assert_eq!(format!("{}", HexFloat(0.0f32)), "0x0p+0");
+    assert_eq!(format!("{}", HexFloat(-0.0f32)), "-0x0p+0");
+    assert_eq!(format!("{}", HexFloat(core::f32::INFINITY)), "inf");
+    assert_eq!(format!("{}", HexFloat(core::f32::NEG_INFINITY)), "-inf");
+    assert_eq!(format!("{}", HexFloat(core::f32::NAN)), "nan");
+
+    // Smallest subnormal: exponent is pinned to the minimum normal exponent.
+    assert_eq!(
+        format!("{}", HexFloat(f32::from_bits(1))),
+        "0x0.000002p-126"
+    );
+
+    assert_eq!(format!("{:x}", HexFloat(1.0f64)), "0x1p+0");
+}
+
+#[test]
+fn test_float_literal_display() {
+    assert_eq!(
+        format!("{}", "0x0".parse::<FloatLiteral>().unwrap()),
+        "0x0p+0"
+    );
+    assert_eq!(
+        format!("{}", "0x1".parse::<FloatLiteral>().unwrap()),
+        "0x1p+0"
+    );
+    assert_eq!(
+        format!("{}", "0x0014.0".parse::<FloatLiteral>().unwrap()),
+        "0x1.4p+4"
+    );
+    assert_eq!(
+        format!("{}", "-0x3.4".parse::<FloatLiteral>().unwrap()),
+        "-0x3.4p+0"
+    );
+    assert_eq!(
+        format!("{}", "0xa.bcp-3".parse::<FloatLiteral>().unwrap()),
+        "0xa.bcp-3"
+    );
+}
+
+#[test]
+fn test_to_normalized_string() {
+    // `to_normalized_string` agrees with `Display`, and round-trips through
+    // `FromStr` to an equal value.
+    for s in &["0x0", "0x0014.0", "-0x3.4", "0xa.bcp-3"] {
+        let literal: FloatLiteral = s.parse().unwrap();
+        let normalized = literal.to_normalized_string();
+        assert_eq!(normalized, format!("{}", literal));
+
+        let reparsed: FloatLiteral = normalized.parse().unwrap();
+        assert_eq!(
+            reparsed.convert::<f64>().inner(),
+            literal.convert::<f64>().inner()
+        );
+    }
+}
+
+#[test]
+fn test_convert_exact() {
+    fn convert_exact(s: &str) -> Result<f32, ConversionError<f32>> {
+        s.parse::<FloatLiteral>().unwrap().convert_exact()
+    }
+
+    assert_eq!(convert_exact("0x1.8"), Ok(1.5));
+    assert_eq!(
+        convert_exact("0x123456789abcdef"),
+        Err(ConversionError {
+            rounded: f32::from_bits(0x5b91_a2b4),
+            ulp_error: 0.25,
+        })
+    );
+    assert_eq!(
+        convert_exact("0x1p10000"),
+        Err(ConversionError {
+            rounded: core::f32::INFINITY,
+            ulp_error: core::f64::INFINITY,
+        })
+    );
+    assert_eq!(
+        convert_exact("0x1p-10000"),
+        Err(ConversionError {
+            rounded: 0.0,
+            ulp_error: -0.5,
+        })
+    );
+}
+
+#[test]
+fn test_floating_suffix() {
+    fn with_suffix(s: &str) -> Result<FloatLiteral, ParseError> {
+        let options = ParseOptions {
+            allow_suffix: true,
+            ..ParseOptions::default()
+        };
+        FloatLiteral::from_chars_with_options(s.chars(), options, &mut 0)
+    }
+
+    assert_eq!(
+        with_suffix("0x1p4").unwrap().suffix(),
+        FloatSuffix::Unsuffixed
+    );
+    assert_eq!(with_suffix("0x1p4f").unwrap().suffix(), FloatSuffix::F32);
+    assert_eq!(with_suffix("0x1p4F").unwrap().suffix(), FloatSuffix::F32);
+    assert_eq!(with_suffix("0x1p4l").unwrap().suffix(), FloatSuffix::F64);
+    assert_eq!(with_suffix("0x1p4L").unwrap().suffix(), FloatSuffix::F64);
+    assert_eq!(with_suffix("0x1p4h").unwrap().suffix(), FloatSuffix::F16);
+    assert_eq!(with_suffix("0x1p4H").unwrap().suffix(), FloatSuffix::F16);
+
+    // Without `allow_suffix`, the trailing suffix is simply left unconsumed.
+    let mut consumed = 0;
+    FloatLiteral::from_chars("0x1p4f".chars(), '.', &mut consumed).unwrap();
+    assert_eq!(consumed, 5);
+
+    assert_eq!(
+        with_suffix("0x1p4f").unwrap().convert_to_suffixed::<f32>(),
+        Ok(ConversionResult::Precise(16.0))
+    );
+    assert!(with_suffix("0x1p4f")
+        .unwrap()
+        .convert_to_suffixed::<f64>()
+        .is_err());
+
+    // A literal with no suffix converts to any requested format.
+    assert_eq!(
+        with_suffix("0x1p4").unwrap().convert_to_suffixed::<f64>(),
+        Ok(ConversionResult::Precise(16.0))
+    );
+}
+
+#[test]
+fn test_ulp_error() {
+    fn ulp_error(is_positive: bool, mantissa: u64, exponent: i32) -> f64 {
+        let mut digits = Vec::new();
+        let mut mantissa = mantissa;
+        while mantissa > 0 {
+            digits.push((mantissa % 16) as u8);
+            mantissa /= 16;
+        }
+        digits.reverse();
+        let decimal_offset = digits.len() as i32;
+        let lit = FloatLiteral::create(is_positive, digits, decimal_offset, exponent);
+        match lit.convert::<f64>() {
+            ConversionResult::Imprecise { ulp_error, .. } => ulp_error,
+            result @ ConversionResult::Precise(_) => {
+                panic!("conversion should have been imprecise (was {:?})", result)
+            }
+        }
+    }
+
+    // A tie that rounds up is 0.5 ULP of the *pre-rounding* mantissa above
+    // the literal, but here rounding also carries into the exponent (as
+    // documented on this mantissa in `test_convert_hexf64`), which doubles
+    // the size of an ULP. So the error relative to the result is 0.25 ULP.
+    assert_eq!(ulp_error(true, 0x003f_ffff_ffff_ffff, 0), 0.25);
+    assert_eq!(ulp_error(false, 0x003f_ffff_ffff_ffff, 0), -0.25);
+
+    // Overflow to infinity has no finite ULP distance.
+    assert_eq!(
+        ulp_error(true, 0x001f_ffff_ffff_ffff, 971 + 1),
+        f64::INFINITY
+    );
+    assert_eq!(
+        ulp_error(false, 0x001f_ffff_ffff_ffff, 971 + 1),
+        f64::NEG_INFINITY
     );
 }
 
@@ -415,6 +676,137 @@ fn test_consumed() {
     assert_eq!(consumed("-0x0p3    "), 6);
 }
 
+#[test]
+fn test_from_bytes() {
+    fn from_bytes(b: &[u8]) -> Result<(FloatLiteral, usize), ParseError> {
+        let mut consumed = 0;
+        let literal = FloatLiteral::from_bytes(b, b'.', &mut consumed)?;
+        Ok((literal, consumed))
+    }
+
+    // Agrees with the char-based parser on well-formed input.
+    for s in ["0x3.4", "-0x1p-128", "0x123456789abcdef", "0x0.01p8"] {
+        let (byte_literal, byte_consumed) = from_bytes(s.as_bytes()).unwrap();
+        let mut char_consumed = 0;
+        let char_literal = FloatLiteral::from_chars(s.chars(), '.', &mut char_consumed).unwrap();
+        assert_eq!(byte_consumed, char_consumed);
+        assert_eq!(byte_literal.convert::<f64>(), char_literal.convert::<f64>());
+    }
+
+    // Invalid UTF-8 trailing bytes don't need to be decoded: the parser
+    // stops cleanly at the first byte that doesn't match the grammar.
+    let (literal, consumed) = from_bytes(b"0x1p4\xff\xfe").unwrap();
+    assert_eq!(consumed, 5);
+    assert_eq_float!(literal.convert::<f32>().inner(), 16.0);
+
+    let (literal, consumed) = from_bytes(b"-0x0.8\xc0").unwrap();
+    assert_eq!(consumed, 6);
+    assert_eq_float!(literal.convert::<f32>().inner(), -0.5);
+
+    // Errors still report a byte index, same as the char-based parser.
+    assert_eq!(
+        from_bytes(b"0x\xff").unwrap_err().kind,
+        ParseErrorKind::MissingDigits
+    );
+    assert_eq!(
+        from_bytes(b"0x1p-\xff").unwrap_err().kind,
+        ParseErrorKind::MissingExponent
+    );
+
+    // `from_bytes_with_options` supports the same options as the char-based
+    // parser, e.g. underscore separators and the floating-suffix.
+    let options = ParseOptions {
+        allow_underscores: true,
+        allow_suffix: true,
+        ..ParseOptions::default()
+    };
+    let mut consumed = 0;
+    let literal =
+        FloatLiteral::from_bytes_with_options(b"0x1_0p1_0f\xff", options, &mut consumed).unwrap();
+    assert_eq!(consumed, 10);
+    assert_eq!(literal.suffix(), FloatSuffix::F32);
+    assert_eq_float!(literal.convert::<f32>().inner(), 16384.0);
+}
+
+#[test]
+fn test_number_literal() {
+    fn number(s: &str) -> Result<NumberLiteral, ParseError> {
+        parse_number_literal(s.chars(), ParseOptions::default(), &mut 0)
+    }
+
+    fn integer(s: &str) -> (bool, u64, IntegerSuffix) {
+        match number(s).unwrap() {
+            NumberLiteral::Integer(i) => (i.is_positive(), i.value(), i.suffix()),
+            NumberLiteral::Float(f) => panic!("expected an integer, got a float: {:?}", f),
+        }
+    }
+
+    // No decimal point or exponent: an unsuffixed integer, checked against
+    // 32 bits.
+    assert_eq!(integer("0xFF"), (true, 255, IntegerSuffix::Unsuffixed));
+    assert_eq!(integer("-0x10"), (false, 16, IntegerSuffix::Unsuffixed));
+    assert_eq!(
+        integer("0xFFFFFFFF"),
+        (true, 0xFFFF_FFFF, IntegerSuffix::Unsuffixed)
+    );
+
+    // A decimal point or exponent still parses as a float.
+    match number("0x1.8").unwrap() {
+        NumberLiteral::Float(f) => {
+            assert_eq_float!(f.convert::<f32>().inner(), 1.5);
+        }
+        NumberLiteral::Integer(i) => panic!("expected a float, got an integer: {:?}", i),
+    }
+    match number("0x1p4").unwrap() {
+        NumberLiteral::Float(f) => {
+            assert_eq_float!(f.convert::<f32>().inner(), 16.0);
+        }
+        NumberLiteral::Integer(i) => panic!("expected a float, got an integer: {:?}", i),
+    }
+
+    // Without a suffix, a value that needs more than 32 bits overflows...
+    assert_eq!(
+        number("0x100000000").unwrap_err().kind,
+        ParseErrorKind::IntegerOverflow
+    );
+    // ...but the same value is fine with an explicit 64-bit suffix.
+    assert_eq!(
+        integer("0x100000000u64"),
+        (true, 0x1_0000_0000, IntegerSuffix::U64)
+    );
+    assert_eq!(
+        integer("0x100000000i64"),
+        (true, 0x1_0000_0000, IntegerSuffix::I64)
+    );
+
+    // Bare `u`/`i` are equivalent to `u32`/`i32`.
+    assert_eq!(integer("0xFFu"), (true, 255, IntegerSuffix::U32));
+    assert_eq!(integer("0xFFi"), (true, 255, IntegerSuffix::I32));
+    assert_eq!(integer("0xFFu32"), (true, 255, IntegerSuffix::U32));
+    assert_eq!(integer("0xFFi32"), (true, 255, IntegerSuffix::I32));
+
+    // A suffixed 32-bit literal still overflows at the same boundary as an
+    // unsuffixed one.
+    assert_eq!(
+        number("0x100000000u32").unwrap_err().kind,
+        ParseErrorKind::IntegerOverflow
+    );
+
+    // `allow_underscores` is honored for integers too.
+    let options = ParseOptions {
+        allow_underscores: true,
+        ..ParseOptions::default()
+    };
+    let mut consumed = 0;
+    match parse_number_literal("0xFF_FFu64".chars(), options, &mut consumed).unwrap() {
+        NumberLiteral::Integer(i) => {
+            assert_eq!(i.value(), 0xFFFF);
+            assert_eq!(consumed, 10);
+        }
+        NumberLiteral::Float(f) => panic!("expected an integer, got a float: {:?}", f),
+    }
+}
+
 #[cfg(feature = "std")]
 mod libc_funcs {
     use std::ffi;